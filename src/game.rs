@@ -3,7 +3,11 @@ use rand::Rng;
 use std::collections::VecDeque;
 use rand::seq::IteratorRandom;
 use itertools::iproduct;
+use std::fs;
+use std::io;
+use std::path::Path;
 
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{FromRepr, EnumIter};
 
@@ -13,7 +17,39 @@ pub enum GameError {
     InvalidCell,
 }
 
-#[derive(Clone, Copy, Debug, FromRepr, EnumIter, PartialEq)]
+/// Errors that can happen while loading a level from JSON5: either the document itself is
+/// malformed, or it parsed fine but describes an inconsistent board (e.g. a hand-edited
+/// `width`/`height` that doesn't match the number of cells in `grid`).
+#[derive(Debug)]
+pub enum LevelError {
+    Json5(json5::Error),
+    SizeMismatch { width: usize, height: usize, grid_len: usize },
+    EmptyBoard,
+}
+
+impl std::fmt::Display for LevelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LevelError::Json5(e) => write!(f, "{}", e),
+            LevelError::SizeMismatch { width, height, grid_len } => write!(
+                f,
+                "grid has {} cells, but width ({}) * height ({}) = {}",
+                grid_len, width, height, width * height
+            ),
+            LevelError::EmptyBoard => write!(f, "width and height must both be at least 1"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<json5::Error> for LevelError {
+    fn from(e: json5::Error) -> Self {
+        LevelError::Json5(e)
+    }
+}
+
+#[derive(Clone, Copy, Debug, FromRepr, EnumIter, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum CellVersion {
     Single = 0,
@@ -28,7 +64,7 @@ impl Distribution<CellVersion> for Standard {
     }
 }
 
-#[derive(Clone, Copy, Debug, FromRepr, EnumIter, PartialEq)]
+#[derive(Clone, Copy, Debug, FromRepr, EnumIter, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum CellOrientation {
     North = 0,
@@ -74,7 +110,7 @@ impl CellOrientation{
 
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Cell {
     pub version: CellVersion,
     pub orientation: CellOrientation,
@@ -104,11 +140,11 @@ impl Cell{
             .collect()
     }
 
-    pub fn matches_constraints(&self, constraints: &Vec<(CellOrientation, bool)>) -> bool{
+    pub fn matches_constraints(&self, constraints: &[(CellOrientation, bool)]) -> bool{
         let cell_connections = self.connects();
         constraints.iter().all(|(dir, connects)|
             if *connects {
-                cell_connections.iter().any(|d| *d == *dir)
+                cell_connections.contains(dir)
             } else {
                 cell_connections.iter().all(|d| *d != *dir)
             }
@@ -129,7 +165,7 @@ impl Cell{
             .iter()
             .filter(|c| c.matches_constraints(&constraints))
             .choose(&mut rand::thread_rng())
-            .expect(format!("No cell configuration can match those constraints : {:?}", constraints).as_str())
+            .unwrap_or_else(|| panic!("No cell configuration can match those constraints : {:?}", constraints))
             .to_owned()
     }
 }
@@ -140,6 +176,14 @@ pub struct Game {
     grid: Vec<Cell>,
 }
 
+/// Plain-data mirror of `Game` used only to (de)serialize it, since `grid` is private.
+#[derive(Serialize, Deserialize)]
+struct GameData {
+    width: usize,
+    height: usize,
+    grid: Vec<Cell>,
+}
+
 impl Game {
     pub fn new(width: usize, height: usize) -> Game {
         Game {
@@ -157,92 +201,124 @@ impl Game {
         }
     }
 
-    pub fn cells(&self) -> impl Iterator<Item = (usize, usize, &Cell)>{
-        iproduct!(0..self.height, 0..self.width).map(|(x, y)| (x, y, self.get_cell(x, y).unwrap()))
-    }
+    /// Builds a connected, loop-free network covering the whole board via randomized Prim's
+    /// algorithm: grow a spanning tree from a seed cell by repeatedly carving a random frontier
+    /// edge (in-tree cell to out-of-tree neighbor), then pick for each cell the `CellVersion`/
+    /// `CellOrientation` whose `connects()` exactly matches the edges carved into it. The result
+    /// is already solved (every cell's orientation is correct); call `scramble` to turn it into
+    /// a puzzle.
+    pub fn random_valid(width: usize, height: usize) -> Game {
+        // A single cell (or an empty board) has no neighbor to carve a connection to, so there's
+        // nothing for the spanning-tree carve below to do; hand back the default all-`Single`
+        // board as-is instead of asking `cell_for_connections` to match zero required connections
+        // (no `CellVersion` has fewer than one).
+        if width * height <= 1 {
+            return Game::new(width, height);
+        }
 
-    pub fn random_invalid(width: usize, height: usize) -> Game {
-        let mut game = Game::new(width, height);
-        let mut rng = rand::thread_rng();
-        game.grid = (0..(width * height)).map(|_| rng.gen()).collect();
-        game
+        // The degree-cap stitch below isn't guaranteed to find the stranded cell it wants: a
+        // cell can end up boxed in entirely by neighbors that are themselves already at the cap.
+        // That's rare enough we couldn't reproduce it in tens of thousands of generated boards,
+        // but rather than let an unlucky shuffle crash a player's "new puzzle" request, just
+        // reshuffle and try the whole carve again.
+        loop {
+            if let Some(game) = Self::try_random_valid(width, height) {
+                return game;
+            }
+        }
     }
 
+    /// One attempt at the randomized-Prim carve described on `random_valid`. Returns `None` if the
+    /// degree-cap stitch ever strands a cell with no eligible neighbor, so the caller can retry.
+    fn try_random_valid(width: usize, height: usize) -> Option<Game> {
+        // The largest shape, `Triple`, only connects in 3 directions, so no cell may carve more
+        // than this many connections even though an interior cell has 4 neighbors.
+        const MAX_DEGREE: usize = 3;
 
-    pub fn random_valid(width:usize, height: usize) -> Game {
         let mut rng = rand::thread_rng();
-        // Start with an empty canvas
         let mut game = Game::new(width, height);
-        // When a cell is generated, lock it, then, add the neighbors to the explore queue
-        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
-        // Start with the center piece
-        queue.push_front((width/2, height/2));
-        loop{
-            while let Some((x, y)) = queue.pop_front(){
-                // List the hard constraints from cells that are already locked
-                let constraints = game.get_cell_constraints(x, y).into_iter()
-                    .chain(
-                        // Add the cells that are on the queue as they will be reached from another branch
-                        game.get_neighbors(x, y).iter()
-                            .filter_map(|(dir, cell)|
-                                if !cell.locked && queue.iter().any(|pos| *pos == dir.step_from(x, y).unwrap()){
-                                    Some((*dir, false))
-                                } else { None }
-                            )
-                    ).collect();
-
-                // Pick a random cell that matches those constraints
-                let cell = Cell::random_that_matches_constraints(constraints);
-
-                // println!("Locked cell at ({}, {}) - {:?} - {:?}", x, y, cell.version, cell.orientation);
-
-                // Set the new cell
-                game.set_cell(x, y, cell).unwrap();
-                game.set_lock(x, y, true).unwrap();
-
-                // Add the connected cells that are not already locked to the queue
-                for (nx, ny) in game.get_non_locked_connections(x, y){
-                    queue.push_back((nx, ny));
-                }
-            }
 
-            // If the queue is empty, either the whole board is locked, ore some areas are unreachable
-            // We look for an unlocked cell with a non-triple locked neighbor and pick one at random
-            // println!("No more cells in the queue. Checking if we missed some");
-            if let Some((x, y, dir)) = game.cells().filter_map(|(x, y, c)|
-                if c.locked { None }
-                else {
-                    //Found an unlocked cell. Does it have a locked neighbor where we could plug it (so, non-triple)?
-                    game.get_neighbors(x, y).iter()
-                        .find_map(|(dir, nc)|
-                            if nc.locked && nc.version != CellVersion::Triple { Some((x, y, *dir)) }
-                            else {None})
-                }
-                // Now pick one of those cells at random
-            ).choose(&mut rng) {
-                // Found an unlocked cell that is next to a locked cell
-                // println!("Manually forcing a way to fill ({}, {}) by changing the cell at its {:?}", x, y, dir);
-                let (lx, ly) = dir.step_from(x, y).unwrap(); // Coords of the locked cell
-                game.set_lock(lx, ly, false).unwrap();
-                // Get its constraints, add the fact that the unlocked cell MUST be reached
-                let mut constraints = game.get_cell_constraints(lx, ly);
-                constraints.push((dir.reverse(), true));
-                // Find a new cell configuration with those constraints
-                game.set_cell(lx, ly, Cell::random_that_matches_constraints(constraints)).unwrap();
-                // Lock it
-                game.set_lock(lx, ly, true).unwrap();
-                // Add newly accessible cells to the list
-                for (nx, ny) in game.get_non_locked_connections(lx, ly){
-                    queue.push_back((nx, ny));
+        let mut in_tree = vec![false; width * height];
+        let mut required: Vec<Vec<CellOrientation>> = vec![Vec::new(); width * height];
+
+        let frontier_edges = |x: usize, y: usize| -> Vec<(usize, usize, CellOrientation)> {
+            CellOrientation::iter()
+                .filter_map(|dir| dir.step_from(x, y)
+                    .filter(|(nx, ny)| *nx < width && *ny < height)
+                    .map(|_| (x, y, dir)))
+                .collect()
+        };
+
+        let seed = (width / 2, height / 2);
+        in_tree[game.idx(seed.0, seed.1)] = true;
+        let mut frontier = frontier_edges(seed.0, seed.1);
+
+        loop {
+            while !frontier.is_empty() {
+                let i = rng.gen_range(0..frontier.len());
+                let (x, y, dir) = frontier.swap_remove(i);
+                let (nx, ny) = dir.step_from(x, y).unwrap();
+
+                // Drop edges that would push the source past the degree cap, or that lead to an
+                // already-reached cell.
+                if in_tree[game.idx(nx, ny)] || required[game.idx(x, y)].len() >= MAX_DEGREE {
+                    continue;
                 }
 
+                required[game.idx(x, y)].push(dir);
+                required[game.idx(nx, ny)].push(dir.reverse());
+                in_tree[game.idx(nx, ny)] = true;
+                frontier.extend(frontier_edges(nx, ny));
             }
-            else {
-                // No unlocked cells, break out of the loop, our job is done
-                break;
+
+            // The degree cap can strand a cell whose every neighbor has already hit it; stitch
+            // it in through any in-tree neighbor that still has spare capacity, then keep going.
+            let stuck = (0..width * height).find(|&i| !in_tree[i]);
+            let i = match stuck {
+                Some(i) => i,
+                None => break,
+            };
+            let (x, y) = (i % width, i / width);
+
+            let (nx, ny, dir) = CellOrientation::iter()
+                .filter_map(|dir| dir.step_from(x, y)
+                    .filter(|(nx, ny)| *nx < width && *ny < height)
+                    .map(|(nx, ny)| (nx, ny, dir)))
+                .find(|(nx, ny, _)| in_tree[game.idx(*nx, *ny)] && required[game.idx(*nx, *ny)].len() < MAX_DEGREE)?;
+
+            required[game.idx(nx, ny)].push(dir.reverse());
+            required[i].push(dir);
+            in_tree[i] = true;
+            frontier.extend(frontier_edges(x, y));
+        }
+
+        for (i, connections) in required.into_iter().enumerate() {
+            let (x, y) = (i % width, i / width);
+            game.set_cell(x, y, Self::cell_for_connections(connections)).unwrap();
+        }
+
+        Some(game)
+    }
+
+    /// Finds the `Cell` (unlocked, unpowered) whose `connects()` set exactly matches `required`.
+    fn cell_for_connections(mut required: Vec<CellOrientation>) -> Cell {
+        required.sort_by_key(|d| *d as u8);
+        Cell::all_possible().into_iter().find(|cell| {
+            let mut connects = cell.connects();
+            connects.sort_by_key(|d| *d as u8);
+            connects == required
+        }).unwrap_or_else(|| panic!("No cell shape has connections {:?}", required))
+    }
+
+    /// Randomizes the orientation of every non-locked cell, turning a solved layout (as produced
+    /// by `random_valid`) back into a puzzle for the player to solve.
+    pub fn scramble(&mut self) {
+        let mut rng = rand::thread_rng();
+        for cell in self.grid.iter_mut() {
+            if !cell.locked {
+                cell.orientation = rng.gen();
             }
         }
-        game
     }
 
     pub fn get_cell(&self, x: usize, y: usize) -> Option<&Cell> {
@@ -270,53 +346,6 @@ impl Game {
         }
     }
 
-    pub fn get_neighbors(&self, x:usize, y:usize) -> Vec<(CellOrientation, &Cell)>{
-        CellOrientation::iter()
-            .filter_map(|dir|
-                if let Some(cell) = self.get_neighbor_at_direction(x, y, dir) {
-                    Some((dir, cell))
-                } else {
-                    None
-                })
-            .collect()
-    }
-
-    pub fn get_neighbor_at_direction(&self, x: usize, y:usize, direction: CellOrientation) -> Option<&Cell>{
-        if let Some((nx, ny)) = direction.step_from(x, y){
-            self.get_cell(nx as usize, ny as usize)
-        } else {
-            None
-        }
-    }
-
-    pub fn get_non_locked_connections(&self, x:usize, y:usize) -> Vec<(usize, usize)> {
-        self.get_cell(x, y).unwrap()
-            .connects().iter()
-            .filter_map(|dir|
-                if let Some(neigh) = self.get_neighbor_at_direction(x, y, *dir) {
-                    if !neigh.locked { Some(dir.step_from(x, y).unwrap()) } else { None }
-                } else { None }
-            ).collect()
-    }
-
-    pub fn get_cell_constraints(&self, x: usize, y:usize) -> Vec<(CellOrientation, bool)>{
-        CellOrientation::iter().filter_map(|dir|
-            // If there is a neighbor in this direction
-            if let Some(c) = self.get_neighbor_at_direction(x, y, dir){
-                if c.locked {
-                    // Check if it must be connected or not-connected
-                    Some((dir, c.connects().iter().any(|d| d.reverse() == dir)))
-                } else {
-                    // If the cell is not locked, we do what we want
-                    None
-                }
-            } else {
-                // If the cell does not exist (hence, we reached a wall), we must not connect to it
-                Some((dir, false))
-            }
-        ).collect()
-    }
-
     pub fn set_cell_orientation(
         &mut self,
         x: usize,
@@ -327,20 +356,284 @@ impl Game {
             if cell.locked {
                 Err(GameError::CellIsLocked)
             } else {
-                Ok(cell.orientation = orientation)
+                cell.orientation = orientation;
+                Ok(())
             }
         })
     }
 
     pub fn set_lock(&mut self, x: usize, y: usize, lock: bool) -> Result<(), GameError> {
         self.get_mut_cell(x, y)
-            .and_then(|cell| Ok(cell.locked = lock))
+            .map(|cell| cell.locked = lock)
     }
 
+    /// Clears every `powered` flag, then floods power out from `sources`: a cell lights up
+    /// only if it is a source or mutually connects to an already-powered neighbor (both sides
+    /// must list the shared direction in their `connects()`).
+    pub fn propagate_power(&mut self, sources: &[(usize, usize)]) {
+        for cell in self.grid.iter_mut() {
+            cell.powered = false;
+        }
 
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        for &(x, y) in sources {
+            if let Ok(cell) = self.get_mut_cell(x, y) {
+                if !cell.powered {
+                    cell.powered = true;
+                    queue.push_back((x, y));
+                }
+            }
+        }
 
-    pub fn power_cell(&mut self, x: usize, y: usize) -> Result<(), GameError> {
-        self.get_mut_cell(x, y)
-            .and_then(|cell| Ok(cell.powered = true))
+        while let Some((x, y)) = queue.pop_front() {
+            for dir in self.get_cell(x, y).unwrap().connects() {
+                if let Some((nx, ny)) = dir.step_from(x, y) {
+                    if let Some(neighbor) = self.get_cell(nx, ny) {
+                        if !neighbor.powered && neighbor.connects().iter().any(|d| *d == dir.reverse()) {
+                            self.get_mut_cell(nx, ny).unwrap().powered = true;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.grid.iter().all(|cell| cell.powered)
+    }
+
+    fn idx(&self, x: usize, y: usize) -> usize {
+        x + self.width * y
+    }
+
+    /// Orientations of `version` that are distinct in terms of `connects()` (e.g. `Line` only
+    /// has 2: North/South and East/West are the same shape rotated 180 degrees).
+    fn distinct_orientations(version: CellVersion) -> Vec<CellOrientation> {
+        let mut seen: Vec<Vec<CellOrientation>> = Vec::new();
+        let mut result = Vec::new();
+        for orientation in CellOrientation::iter() {
+            let mut connects = Cell { version, orientation, locked: false, powered: false }.connects();
+            connects.sort_by_key(|d| *d as u8);
+            if !seen.contains(&connects) {
+                seen.push(connects);
+                result.push(orientation);
+            }
+        }
+        result
+    }
+
+    fn connects_towards(version: CellVersion, orientation: CellOrientation, dir: CellOrientation) -> bool {
+        Cell { version, orientation, locked: false, powered: false }.connects().contains(&dir)
+    }
+
+    /// Prunes `domains` until a fixpoint: for every edge, if all remaining orientations of one
+    /// side agree the edge is connected (or all agree it isn't), the other side's domain is
+    /// pruned to match. Returns `false` as soon as a domain goes empty (contradiction).
+    fn propagate(&self, domains: &mut [Vec<CellOrientation>]) -> bool {
+        loop {
+            let mut changed = false;
+
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let i = self.idx(x, y);
+                    let version = self.grid[i].version;
+
+                    for dir in CellOrientation::iter() {
+                        let forced = match dir.step_from(x, y).filter(|(nx, ny)| *nx < self.width && *ny < self.height) {
+                            None => Some(false),
+                            Some((nx, ny)) => {
+                                let neighbor_version = self.grid[self.idx(nx, ny)].version;
+                                let reverse = dir.reverse();
+                                let neighbor_domain = &domains[self.idx(nx, ny)];
+                                if neighbor_domain.iter().all(|o| Self::connects_towards(neighbor_version, *o, reverse)) {
+                                    Some(true)
+                                } else if neighbor_domain.iter().all(|o| !Self::connects_towards(neighbor_version, *o, reverse)) {
+                                    Some(false)
+                                } else {
+                                    None
+                                }
+                            }
+                        };
+
+                        if let Some(connects) = forced {
+                            let before = domains[i].len();
+                            domains[i].retain(|o| Self::connects_towards(version, *o, dir) == connects);
+                            if domains[i].len() != before {
+                                changed = true;
+                            }
+                            if domains[i].is_empty() {
+                                return false;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !changed {
+                return true;
+            }
+        }
+    }
+
+    /// Checks whether a fully-determined `domains` (one orientation left per cell) forms a single
+    /// network reaching every cell from `(0, 0)`, the same mutual-agreement flood fill as
+    /// `propagate_power` but reading tentative orientations out of `domains` instead of `grid`.
+    fn domains_connected(&self, domains: &[Vec<CellOrientation>]) -> bool {
+        let mut reached = vec![false; self.width * self.height];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        reached[0] = true;
+        queue.push_back(0);
+
+        while let Some(i) = queue.pop_front() {
+            let (x, y) = (i % self.width, i / self.width);
+            let version = self.grid[i].version;
+            let orientation = domains[i][0];
+
+            for dir in (Cell { version, orientation, locked: false, powered: false }).connects() {
+                if let Some((nx, ny)) = dir.step_from(x, y).filter(|(nx, ny)| *nx < self.width && *ny < self.height) {
+                    let j = self.idx(nx, ny);
+                    if !reached[j] {
+                        let neighbor_version = self.grid[j].version;
+                        let neighbor_orientation = domains[j][0];
+                        if Self::connects_towards(neighbor_version, neighbor_orientation, dir.reverse()) {
+                            reached[j] = true;
+                            queue.push_back(j);
+                        }
+                    }
+                }
+            }
+        }
+
+        reached.iter().all(|&r| r)
+    }
+
+    /// Propagates constraints, then branches on the cell with the smallest remaining domain and
+    /// recurses, backtracking whenever a branch leads to a contradiction or, once every cell is
+    /// determined, the resulting network doesn't reach every cell (the local edge-agreement
+    /// constraints alone allow disconnected loops).
+    fn search(&self, domains: &mut Vec<Vec<CellOrientation>>) -> bool {
+        if !self.propagate(domains) {
+            return false;
+        }
+
+        let undetermined = domains.iter().enumerate().filter(|(_, d)| d.len() > 1).min_by_key(|(_, d)| d.len());
+
+        let i = match undetermined {
+            Some((i, _)) => i,
+            None => return self.domains_connected(domains),
+        };
+
+        for candidate in domains[i].clone() {
+            let mut branch = domains.clone();
+            branch[i] = vec![candidate];
+            if self.search(&mut branch) {
+                *domains = branch;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Finds an orientation for every non-locked cell that makes the whole board a valid,
+    /// fully-connected network, modeling the board as a CSP (see `propagate`/`search`) and
+    /// writing the result into the grid on success.
+    pub fn solve(&mut self) -> bool {
+        let mut domains: Vec<Vec<CellOrientation>> = self.grid.iter()
+            .map(|cell| if cell.locked { vec![cell.orientation] } else { Self::distinct_orientations(cell.version) })
+            .collect();
+
+        if !self.search(&mut domains) {
+            return false;
+        }
+
+        for (i, cell) in self.grid.iter_mut().enumerate() {
+            cell.orientation = domains[i][0];
+        }
+        true
+    }
+
+    /// Serializes `width`, `height` and the full grid (including `locked`) to JSON5, so a puzzle
+    /// can be hand-authored or shared as readable text.
+    pub fn to_json5(&self) -> json5::Result<String> {
+        json5::to_string(&GameData { width: self.width, height: self.height, grid: self.grid.clone() })
+    }
+
+    pub fn from_json5(input: &str) -> Result<Game, LevelError> {
+        let data: GameData = json5::from_str(input)?;
+        if data.width == 0 || data.height == 0 {
+            return Err(LevelError::EmptyBoard);
+        }
+        if data.grid.len() != data.width * data.height {
+            return Err(LevelError::SizeMismatch {
+                width: data.width,
+                height: data.height,
+                grid_len: data.grid.len(),
+            });
+        }
+        Ok(Game { width: data.width, height: data.height, grid: data.grid })
+    }
+
+    pub fn save_to_path(&self, path: &Path) -> io::Result<()> {
+        let json = self.to_json5().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(path, json)
+    }
+
+    pub fn load_from_path(path: &Path) -> io::Result<Game> {
+        let content = fs::read_to_string(path)?;
+        Game::from_json5(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_valid_connects_every_cell() {
+        for &(w, h) in &[(2, 2), (3, 3), (5, 5), (11, 11)] {
+            for _ in 0..20 {
+                let mut game = Game::random_valid(w, h);
+                game.propagate_power(&[(0, 0)]);
+                assert!(game.is_solved(), "a {}x{} board left a cell unreached", w, h);
+            }
+        }
+    }
+
+    #[test]
+    fn random_valid_handles_a_single_cell_board() {
+        let mut game = Game::random_valid(1, 1);
+        game.propagate_power(&[(0, 0)]);
+        assert!(game.is_solved());
+    }
+
+    #[test]
+    fn solve_reconnects_a_scrambled_board() {
+        for _ in 0..20 {
+            let mut game = Game::random_valid(6, 6);
+            game.scramble();
+            assert!(game.solve(), "solve() failed on a board produced by random_valid");
+            game.propagate_power(&[(0, 0)]);
+            assert!(game.is_solved());
+        }
+    }
+
+    #[test]
+    fn json5_round_trip_rejects_size_mismatch() {
+        let game = Game::random_valid(3, 3);
+        let json = game.to_json5().unwrap();
+        let reloaded = Game::from_json5(&json).unwrap();
+        assert_eq!(reloaded.width, 3);
+        assert_eq!(reloaded.height, 3);
+
+        let bad = r#"{width: 3, height: 3, grid: []}"#;
+        assert!(matches!(Game::from_json5(bad), Err(LevelError::SizeMismatch { .. })));
+    }
+
+    #[test]
+    fn json5_round_trip_rejects_an_empty_board() {
+        let empty = r#"{width: 0, height: 0, grid: []}"#;
+        assert!(matches!(Game::from_json5(empty), Err(LevelError::EmptyBoard)));
     }
 }
@@ -1,18 +1,20 @@
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     widgets::{Block, Borders},
     Frame, Terminal,
 };
 
 use crossterm::{
     event,
-    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, KeyCode, MouseButton, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
-use std::{io, time::Duration};
+use std::{io, path::Path, time::Duration};
 use tui::layout::Margin;
 use tui::style::{Color, Style};
 use tui::text::Span;
@@ -20,44 +22,83 @@ use tui::widgets::canvas::{Canvas, Context};
 
 use unicode_segmentation::UnicodeSegmentation;
 
-use crate::game::Game;
+use crate::game::{CellOrientation, Game};
 
 
 pub fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
-    mut game: Game
+    mut game: Game,
+    sources: Vec<(usize, usize)>,
 ) -> io::Result<()> {
+    let mut cursor = (0usize, 0usize);
+
     loop {
-        terminal.draw(|f| ui(f, &game))?;
+        terminal.draw(|f| ui(f, &game, cursor))?;
 
         if event::poll(Duration::from_millis(500))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => {
-                        return Ok(());
-                    }
-                    /*
-                    KeyCode::Down => {
-                        app.y += 1.0;
-                    }
-                    KeyCode::Up => {
-                        app.y -= 1.0;
+            match event::read()? {
+                Event::Key(key) => {
+                    match key.code {
+                        KeyCode::Char('q') => {
+                            return Ok(());
+                        }
+                        KeyCode::Up => {
+                            cursor.1 = cursor.1.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            cursor.1 = (cursor.1 + 1).min(game.height - 1);
+                        }
+                        KeyCode::Left => {
+                            cursor.0 = cursor.0.saturating_sub(1);
+                        }
+                        KeyCode::Right => {
+                            cursor.0 = (cursor.0 + 1).min(game.width - 1);
+                        }
+                        KeyCode::Char(' ') => {
+                            rotate_cell(&mut game, cursor.0, cursor.1, CellOrientation::East);
+                            game.propagate_power(&sources);
+                        }
+                        KeyCode::Char('b') => {
+                            rotate_cell(&mut game, cursor.0, cursor.1, CellOrientation::West);
+                            game.propagate_power(&sources);
+                        }
+                        KeyCode::Char('l') => {
+                            let locked = game.get_cell(cursor.0, cursor.1).unwrap().locked;
+                            game.set_lock(cursor.0, cursor.1, !locked).ok();
+                        }
+                        KeyCode::Char('a') if game.solve() => {
+                            game.propagate_power(&sources);
+                        }
+                        KeyCode::Char('s') => {
+                            game.save_to_path(Path::new("level.json5")).ok();
+                        }
+                        _ => {}
                     }
-                    KeyCode::Right => {
-                        app.x += 1.0;
-                    }
-                    KeyCode::Left => {
-                        app.x -= 1.0;
+                }
+                Event::Mouse(mouse) => {
+                    if let MouseEventKind::Down(MouseButton::Left) = mouse.kind {
+                        if let Some((x, y)) = mouse_to_cell(terminal.size()?, &game, mouse.column, mouse.row) {
+                            rotate_cell(&mut game, x, y, CellOrientation::East);
+                            game.propagate_power(&sources);
+                        }
                     }
-                    */
-                    _ => {}
                 }
+                _ => {}
             }
         }
     }
 }
 
-fn paint_game(ctx: &mut Context, game: &Game){
+/// Rotates the cell at `(x, y)` by `direction` (reusing `CellOrientation::rotate`), unless it
+/// is locked. Errors (locked or out-of-bounds cell) are silently ignored: a no-op rotation.
+fn rotate_cell(game: &mut Game, x: usize, y: usize, direction: CellOrientation) {
+    if let Some(cell) = game.get_cell(x, y) {
+        let new_orientation = cell.orientation.rotate(direction);
+        game.set_cell_orientation(x, y, new_orientation).ok();
+    }
+}
+
+fn paint_game(ctx: &mut Context, game: &Game, cursor: (usize, usize)){
     let symbols = "╵╶╷╴└┌┐┘│─│─┬┤┴├";
 
     for y in 0..game.height {
@@ -65,9 +106,9 @@ fn paint_game(ctx: &mut Context, game: &Game){
             if let Some(cell) = game.get_cell(x, y){
                 let symbol = symbols.graphemes(true)
                                     .nth(cell.version as usize * 4 + cell.orientation as usize)
-                                    .expect(format!("No char at position {}", cell.version as usize * 4 + cell.orientation as usize).as_str());
+                                    .unwrap_or_else(|| panic!("No char at position {}", cell.version as usize * 4 + cell.orientation as usize));
                 let fg = if cell.powered {Color::LightBlue} else {Color::White};
-                let bg = if cell.locked {Color::DarkGray} else {Color::Black};
+                let bg = if (x, y) == cursor {Color::Yellow} else if cell.locked {Color::DarkGray} else {Color::Black};
 
                 ctx.print(x as f64, y as f64,Span::styled(symbol, Style::default().fg(fg).bg(bg)));
             } else {
@@ -81,8 +122,46 @@ fn paint_game(ctx: &mut Context, game: &Game){
 
 }
 
+/// Returns the `board_area` a `Canvas` for `game` would be painted into within `terminal_size`,
+/// mirroring the layout computed by `ui`.
+fn canvas_area(terminal_size: Rect, game_width: usize) -> Rect {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(game_width as u16 + 4), Constraint::Min(0)].as_ref())
+        .split(terminal_size);
+
+    chunks[0].inner(&Margin{vertical: 1, horizontal: 1})
+}
+
+/// Maps a mouse click at terminal `(column, row)` back to board `(x, y)`, inverting the linear
+/// `x_bounds`/`y_bounds` scaling `ui` applies to the canvas (whose y axis grows upward).
+fn mouse_to_cell(terminal_size: Rect, game: &Game, column: u16, row: u16) -> Option<(usize, usize)> {
+    let area = canvas_area(terminal_size, game.width);
+    if column < area.x || row < area.y || column >= area.x + area.width || row >= area.y + area.height {
+        return None;
+    }
+
+    let x_bounds = [-2.0, area.width as f64 - 2.0];
+    let y_bounds = [-1.0, area.height as f64 - 1.0];
+
+    let col = (column - area.x) as f64;
+    let line = (row - area.y) as f64;
+
+    // The canvas truncates when it places a label, so the board coordinate printed on this
+    // column/row is the smallest whole offset that scales onto it: round the inverse up.
+    let x = x_bounds[0] + (col * (x_bounds[1] - x_bounds[0]) / (area.width as f64 - 1.0).max(1.0)).ceil();
+    let y = y_bounds[1] - (line * (y_bounds[1] - y_bounds[0]) / (area.height as f64 - 1.0).max(1.0)).ceil();
 
-fn ui<B: Backend>(f: &mut Frame<B>, game: &Game) {
+    let (x, y) = (x.round() as isize, y.round() as isize);
+    if x >= 0 && y >= 0 && (x as usize) < game.width && (y as usize) < game.height {
+        Some((x as usize, y as usize))
+    } else {
+        None
+    }
+}
+
+
+fn ui<B: Backend>(f: &mut Frame<B>, game: &Game, cursor: (usize, usize)) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Length(game.width as u16 + 4), Constraint::Min(0)].as_ref())
@@ -90,16 +169,18 @@ fn ui<B: Backend>(f: &mut Frame<B>, game: &Game) {
 
     let board_area = chunks[0].inner(&Margin{vertical: 1, horizontal: 1});
 
+    let title = if game.is_solved() { "Board - solved!" } else { "Board" };
+
     let canvas = Canvas::default()
-        .block(Block::default().borders(Borders::ALL).title("Board"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .x_bounds([-2.0, board_area.width as f64 - 2.0])
         .y_bounds([-1.0, board_area.height as f64 - 1.0])
-        .paint(|ctx| { paint_game(ctx, game) });
+        .paint(|ctx| { paint_game(ctx, game, cursor) });
     f.render_widget(canvas, chunks[0]);
 }
 
 
-pub fn run(mut game: Game) -> Result<(), io::Error> {
+pub fn run(game: Game, sources: Vec<(usize, usize)>) -> Result<(), io::Error> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -107,7 +188,7 @@ pub fn run(mut game: Game) -> Result<(), io::Error> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    run_app(&mut terminal, game)?;
+    run_app(&mut terminal, game, sources)?;
 
     // restore terminal
     disable_raw_mode()?;
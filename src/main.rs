@@ -1,16 +1,27 @@
+use std::env;
 use std::io;
-use itertools::Itertools;
+use std::path::Path;
 
 mod interface;
 mod game;
 
 use game::Game;
-use crate::game::CellOrientation;
 
 fn main() -> Result<(), io::Error> {
-    let mut game = Game::random_valid(11, 11);
-    game.powered_cells().iter().for_each(|(x, y)| game.power_cell(*x, *y).ok().expect("Could not power this cell"));
-    interface::run(game).expect("Interface crashed");
+    let mut game = match env::args().nth(1) {
+        // A level file was given: load it as-is, already in whatever state it was saved in.
+        Some(path) => Game::load_from_path(Path::new(&path))?,
+        // Otherwise generate a fresh puzzle to solve.
+        None => {
+            let mut game = Game::random_valid(11, 11);
+            game.scramble();
+            game
+        }
+    };
+
+    let sources = vec![(game.width / 2, game.height / 2)];
+    game.propagate_power(&sources);
+    interface::run(game, sources).expect("Interface crashed");
 
     Ok(())
 }